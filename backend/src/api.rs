@@ -7,11 +7,35 @@ use axum::{
 use axum_extra::extract::Multipart;
 use tokio_util::io::ReaderStream;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
 use std::sync::Arc;
-use std::borrow::Cow;
-use bytes::Bytes;
 
-use crate::xmap::{XmapCache, XmapFileSet, hash_content, stream_matches_multi};
+use crate::xmap::{
+    XmapCache, XmapFileSet, XmapMatch, MatchFeed, StreamingXmapParser, StreamParseError,
+    match_id, stream_matches_multi,
+};
+
+/// Per-file upload cap, in bytes. A field is rejected the moment it crosses
+/// this threshold instead of after the whole field has been received.
+/// Overridable via `XMAP_MAX_FIELD_BYTES` for deployments with tighter or
+/// looser RAM budgets.
+fn max_field_bytes() -> usize {
+    std::env::var("XMAP_MAX_FIELD_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(4 * 1024 * 1024 * 1024)
+}
+
+/// Encodes `match_data` as a length-delimited bincode frame and writes it to
+/// the duplex stream. Returns `false` if the client has gone away so the
+/// caller can stop feeding it.
+async fn write_match_frame(writer: &mut tokio::io::DuplexStream, match_data: &XmapMatch) -> bool {
+    let Ok(bytes) = bincode::serialize(match_data) else {
+        return false;
+    };
+    let len = (bytes.len() as u32).to_le_bytes();
+    writer.write_all(&len).await.is_ok() && writer.write_all(&bytes).await.is_ok()
+}
 
 /// Streams XMAP matches for uploaded files
 ///
@@ -23,27 +47,48 @@ use crate::xmap::{XmapCache, XmapFileSet, hash_content, stream_matches_multi};
 /// * `Result<Response<Body>, StatusCode>` - Streaming response or error status
 ///
 /// # Process
-/// 1. Extracts 2-3 XMAP files from multipart form
-/// 2. Parses files and builds indices
+/// 1. Pulls 2-3 XMAP fields from the multipart body one chunk at a time,
+///    parsing and hashing each field as its bytes arrive rather than
+///    buffering the whole upload first
+/// 2. Builds indices
 /// 3. Streams matches via duplex channel
 /// 4. Caches results for future requests
 pub async fn stream_xmap_matches(
     State(cache): State<Arc<XmapCache>>,
     mut multipart: Multipart,
 ) -> Result<Response<Body>, StatusCode> {
-    let mut files: Vec<(String, Bytes)> = Vec::new();
+    let byte_cap = max_field_bytes();
+    let mut file_hashes = Vec::with_capacity(3);
+    let mut file_records = Vec::with_capacity(3);
+
+    while let Some(mut field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+        if file_hashes.len() == 3 {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let mut parser = StreamingXmapParser::new(byte_cap);
+        while let Some(chunk) = field.chunk().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+            parser.feed(&chunk).map_err(|e| match e {
+                StreamParseError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+                StreamParseError::Invalid(_) => StatusCode::BAD_REQUEST,
+            })?;
+        }
 
-    while let Some(field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
-        let name = field.name().unwrap_or("").to_string();
-        let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-        files.push((name, bytes));
+        let (hash, records, chr_lengths) = parser.finish().map_err(|e| match e {
+            StreamParseError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            StreamParseError::Invalid(_) => StatusCode::BAD_REQUEST,
+        })?;
+        let (records, _) = cache.get_or_insert_streamed(hash, records, chr_lengths);
+
+        file_hashes.push(hash);
+        file_records.push(records);
     }
 
-    if files.is_empty() || files.len() > 3 {
+    if file_records.is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    if files.len() == 1 {
+    if file_records.len() == 1 {
         return Ok(Response::builder()
             .status(StatusCode::OK)
             .header("Content-Type", "application/octet-stream")
@@ -51,30 +96,6 @@ pub async fn stream_xmap_matches(
             .unwrap());
     }
 
-    let mut file_hashes = Vec::with_capacity(files.len());
-    let mut file_records = Vec::with_capacity(files.len());
-
-    for (name, bytes) in files {
-        let content_str = std::str::from_utf8(&bytes).map_err(|_| StatusCode::BAD_REQUEST)?;
-        let hash = hash_content(content_str);
-        file_hashes.push(hash);
-
-        let bytes_arc = Arc::new(bytes);
-        let records = tokio::task::spawn_blocking({
-            let cache = Arc::clone(&cache);
-            let content = Arc::clone(&bytes_arc);
-            move || {
-                let s: Cow<str> = Cow::Borrowed(std::str::from_utf8(&content).unwrap());
-                cache.get_or_parse(hash, &s)
-            }
-        })
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-            .map_err(|_| StatusCode::BAD_REQUEST)?;
-
-        file_records.push(records);
-    }
-
     let mut all_records_with_indices = Vec::with_capacity(file_records.len());
 
     for (idx, records) in file_records.into_iter().enumerate() {
@@ -101,22 +122,66 @@ pub async fn stream_xmap_matches(
     let (mut writer, reader) = tokio::io::duplex(131072);
 
     let cache_key = file_hashes.into_boxed_slice();
-    tokio::spawn(async move {
-        let rx = stream_matches_multi(fileset);
-
-        while let Ok(match_data) = rx.recv() {
-            let match_arc = Arc::new(match_data.clone());
-            cache.cache_match(cache_key.clone(), match_arc);
-
-            if let Ok(bytes) = bincode::serialize(&match_data) {
-                let len = (bytes.len() as u32).to_le_bytes();
-                if writer.write_all(&len).await.is_err() { break; }
-                if writer.write_all(&bytes).await.is_err() { break; }
-            } else {
-                break;
-            }
+    let feed = cache.join_match_stream(cache_key.clone());
+
+    match feed {
+        MatchFeed::Producer(tx) => {
+            tokio::spawn(async move {
+                let rx = stream_matches_multi(fileset);
+
+                while let Ok(match_data) = rx.recv() {
+                    let match_arc = Arc::new(match_data);
+                    cache.cache_match(cache_key.clone(), Arc::clone(&match_arc));
+                    let _ = tx.send(Arc::clone(&match_arc));
+
+                    if !write_match_frame(&mut writer, &match_arc).await {
+                        break;
+                    }
+                }
+
+                cache.finish_match_stream(&cache_key);
+            });
         }
-    });
+        MatchFeed::Subscriber(mut broadcast_rx) => {
+            tokio::spawn(async move {
+                let mut seen = std::collections::HashSet::new();
+
+                for match_arc in cache.cached_matches(&cache_key) {
+                    seen.insert(match_id(&match_arc));
+                    if !write_match_frame(&mut writer, &match_arc).await {
+                        return;
+                    }
+                }
+
+                loop {
+                    match broadcast_rx.recv().await {
+                        Ok(match_arc) => {
+                            if seen.insert(match_id(&match_arc))
+                                && !write_match_frame(&mut writer, &match_arc).await
+                            {
+                                break;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            // We fell behind the producer's broadcast channel and
+                            // missed some sends outright, not just reordered them.
+                            // The cache holds every match the producer has pushed
+                            // so far, so replay whatever we haven't seen yet from
+                            // there before resuming the live feed.
+                            for match_arc in cache.cached_matches(&cache_key) {
+                                if seen.insert(match_id(&match_arc))
+                                    && !write_match_frame(&mut writer, &match_arc).await
+                                {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+    }
 
     let stream = ReaderStream::new(reader);
     let body = Body::from_stream(stream);
@@ -4,6 +4,9 @@ use crossbeam::channel;
 use crossbeam::queue::SegQueue;
 use rayon::prelude::*;
 use std::sync::Arc;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use tokio::sync::broadcast;
 
 /// Represents a single XMAP record from parsed files
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +52,47 @@ pub struct MatchedRecord {
     pub ref_len: f64,
 }
 
+/// Parses a single XMAP line into `records`/`chromosome_lengths`, skipping
+/// comments, blank lines, and rows with fewer than 12 columns; a column
+/// that fails to parse is returned as an error. Shared by the
+/// whole-buffer parser and [`StreamingXmapParser`] so both paths apply
+/// identical parsing rules.
+fn parse_xmap_line(
+    line: &str,
+    records: &DashMap<u32, Arc<XmapRecord>>,
+    chromosome_lengths: &DashMap<u8, f64>,
+) -> Result<(), String> {
+    if line.starts_with('#') || line.trim().is_empty() {
+        return Ok(());
+    }
+
+    let fields: Box<[&str]> = line.split('\t').collect();
+    if fields.len() < 12 {
+        return Ok(());
+    }
+
+    let ref_contig_id: u8 = fields[2].parse().map_err(|e| format!("Parse RefContigID: {}", e))?;
+    let ref_len: f64 = fields[11].parse().map_err(|e| format!("Parse RefLen: {}", e))?;
+
+    chromosome_lengths.insert(ref_contig_id, ref_len);
+
+    let record = Arc::new(XmapRecord {
+        xmap_entry_id: fields[0].parse().map_err(|e| format!("Parse XmapEntryID: {}", e))?,
+        qry_contig_id: fields[1].parse().map_err(|e| format!("Parse QryContigID: {}", e))?,
+        ref_contig_id,
+        qry_start_pos: fields[3].parse().map_err(|e| format!("Parse QryStartPos: {}", e))?,
+        qry_end_pos: fields[4].parse().map_err(|e| format!("Parse QryEndPos: {}", e))?,
+        ref_start_pos: fields[5].parse().map_err(|e| format!("Parse RefStartPos: {}", e))?,
+        ref_end_pos: fields[6].parse().map_err(|e| format!("Parse RefEndPos: {}", e))?,
+        orientation: fields[7].chars().next().unwrap_or('+'),
+        confidence: fields[8].parse().map_err(|e| format!("Parse Confidence: {}", e))?,
+        ref_len,
+    });
+
+    records.insert(record.xmap_entry_id, Arc::clone(&record));
+    Ok(())
+}
+
 /// Parses XMAP file content into structured records
 ///
 /// # Arguments
@@ -65,36 +109,85 @@ pub fn parse_xmap_file(content: &str) -> Result<(Arc<DashMap<u32, Arc<XmapRecord
 
     content
         .par_lines()
-        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
-        .try_for_each(|line| -> Result<(), String> {
-            let fields: Box<[&str]> = line.split('\t').collect();
-            if fields.len() < 12 {
-                return Ok(());
-            }
+        .try_for_each(|line| parse_xmap_line(line, &records, &chromosome_lengths))?;
 
-            let ref_contig_id: u8 = fields[2].parse().map_err(|e| format!("Parse RefContigID: {}", e))?;
-            let ref_len: f64 = fields[11].parse().map_err(|e| format!("Parse RefLen: {}", e))?;
+    Ok((Arc::new(records), Arc::new(chromosome_lengths)))
+}
 
-            chromosome_lengths.insert(ref_contig_id, ref_len);
+/// Error produced while feeding a chunk to a [`StreamingXmapParser`].
+#[derive(Debug)]
+pub enum StreamParseError {
+    /// The field exceeded its configured per-file byte cap before finishing.
+    TooLarge { cap: usize },
+    /// The field content could not be parsed as XMAP.
+    Invalid(String),
+}
+
+/// Incrementally parses XMAP content as it arrives in upload chunks,
+/// instead of requiring the whole file to be buffered first. Computes the
+/// content hash on the fly (over the same bytes, in order) so the cache key
+/// is available the moment the last chunk is fed, with no second pass.
+pub struct StreamingXmapParser {
+    records: DashMap<u32, Arc<XmapRecord>>,
+    chromosome_lengths: DashMap<u8, f64>,
+    pending_line: Vec<u8>,
+    hasher: DefaultHasher,
+    bytes_seen: usize,
+    byte_cap: usize,
+}
 
-            let record = Arc::new(XmapRecord {
-                xmap_entry_id: fields[0].parse().map_err(|e| format!("Parse XmapEntryID: {}", e))?,
-                qry_contig_id: fields[1].parse().map_err(|e| format!("Parse QryContigID: {}", e))?,
-                ref_contig_id,
-                qry_start_pos: fields[3].parse().map_err(|e| format!("Parse QryStartPos: {}", e))?,
-                qry_end_pos: fields[4].parse().map_err(|e| format!("Parse QryEndPos: {}", e))?,
-                ref_start_pos: fields[5].parse().map_err(|e| format!("Parse RefStartPos: {}", e))?,
-                ref_end_pos: fields[6].parse().map_err(|e| format!("Parse RefEndPos: {}", e))?,
-                orientation: fields[7].chars().next().unwrap_or('+'),
-                confidence: fields[8].parse().map_err(|e| format!("Parse Confidence: {}", e))?,
-                ref_len,
-            });
+impl StreamingXmapParser {
+    /// Creates a parser that rejects the field as soon as it exceeds
+    /// `byte_cap` bytes, rather than after the whole field is received.
+    pub fn new(byte_cap: usize) -> Self {
+        Self {
+            records: DashMap::new(),
+            chromosome_lengths: DashMap::new(),
+            pending_line: Vec::new(),
+            hasher: DefaultHasher::new(),
+            bytes_seen: 0,
+            byte_cap,
+        }
+    }
 
-            records.insert(record.xmap_entry_id, Arc::clone(&record));
-            Ok(())
-        })?;
+    /// Feeds the next chunk of raw field bytes: hashes it, appends it to
+    /// the carry-over buffer, and parses any complete lines it now
+    /// contains. An incomplete trailing line is held over to the next call.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), StreamParseError> {
+        self.bytes_seen += chunk.len();
+        if self.bytes_seen > self.byte_cap {
+            return Err(StreamParseError::TooLarge { cap: self.byte_cap });
+        }
 
-    Ok((Arc::new(records), Arc::new(chromosome_lengths)))
+        self.hasher.write(chunk);
+        self.pending_line.extend_from_slice(chunk);
+
+        let mut consumed = 0;
+        while let Some(pos) = self.pending_line[consumed..].iter().position(|&b| b == b'\n') {
+            let line_end = consumed + pos;
+            let line = std::str::from_utf8(&self.pending_line[consumed..line_end])
+                .map_err(|e| StreamParseError::Invalid(format!("Invalid UTF-8 in XMAP line: {}", e)))?;
+            parse_xmap_line(line, &self.records, &self.chromosome_lengths)
+                .map_err(StreamParseError::Invalid)?;
+            consumed = line_end + 1;
+        }
+        self.pending_line.drain(..consumed);
+
+        Ok(())
+    }
+
+    /// Finalizes parsing: flushes a trailing line with no terminator and
+    /// returns the content hash alongside the parsed records.
+    pub fn finish(mut self) -> Result<(u64, Arc<DashMap<u32, Arc<XmapRecord>>>, Arc<DashMap<u8, f64>>), StreamParseError> {
+        if !self.pending_line.is_empty() {
+            let line = std::str::from_utf8(&self.pending_line)
+                .map_err(|e| StreamParseError::Invalid(format!("Invalid UTF-8 in XMAP line: {}", e)))?;
+            parse_xmap_line(line, &self.records, &self.chromosome_lengths)
+                .map_err(StreamParseError::Invalid)?;
+        }
+
+        Ok((self.hasher.finish(), Arc::new(self.records), Arc::new(self.chromosome_lengths)))
+    }
 }
 
 /// Builds index mapping query contig IDs to their records
@@ -281,12 +374,32 @@ pub fn stream_matches_multi(
     rx
 }
 
+/// Computes the per-match cache key used to dedup a match within a file-set
+/// result, derived the same way `XmapCache::cache_match` always has.
+pub(crate) fn match_id(match_data: &XmapMatch) -> u64 {
+    (match_data.qry_contig_id as u64) << 32 | (match_data.records[0].qry_start_pos as u64)
+}
+
+/// Capacity of the broadcast channel backing an in-flight match computation.
+/// Subscribers that fall more than this many matches behind the producer
+/// will observe a `Lagged` error and skip ahead rather than block it.
+const MATCH_BROADCAST_CAPACITY: usize = 1024;
+
+/// Either role a caller can take when joining the matches for a cache key:
+/// the first caller becomes the `Producer` driving the computation, later
+/// callers for the same key become `Subscriber`s of its broadcast.
+pub enum MatchFeed {
+    Producer(broadcast::Sender<Arc<XmapMatch>>),
+    Subscriber(broadcast::Receiver<Arc<XmapMatch>>),
+}
+
 /// Cache manager for XMAP parsing and matching results
 pub struct XmapCache {
     pub parsed_files: Arc<DashMap<u64, Arc<DashMap<u32, Arc<XmapRecord>>>>>,
     pub chromosome_lengths: Arc<DashMap<u64, Arc<DashMap<u8, f64>>>>,
     pub indices: Arc<DashMap<u64, Arc<DashMap<u32, Arc<DashMap<u32, Arc<XmapRecord>>>>>>>,
     pub match_cache: Arc<DashMap<Box<[u64]>, Arc<DashMap<u64, Arc<XmapMatch>>>>>,
+    in_flight: Arc<DashMap<Box<[u64]>, broadcast::Sender<Arc<XmapMatch>>>>,
 }
 
 impl XmapCache {
@@ -297,9 +410,46 @@ impl XmapCache {
             chromosome_lengths: Arc::new(DashMap::new()),
             indices: Arc::new(DashMap::new()),
             match_cache: Arc::new(DashMap::new()),
+            in_flight: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Joins the match computation for `key`: if one is already running,
+    /// subscribes to its broadcast; otherwise registers this caller as the
+    /// producer. Atomic under the `in_flight` map's per-shard lock, so two
+    /// concurrent requests for the same key can never both become producers.
+    pub fn join_match_stream(&self, key: Box<[u64]>) -> MatchFeed {
+        match self.in_flight.entry(key) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => {
+                MatchFeed::Subscriber(entry.get().subscribe())
+            }
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                let (tx, _rx) = broadcast::channel(MATCH_BROADCAST_CAPACITY);
+                entry.insert(tx.clone());
+                MatchFeed::Producer(tx)
+            }
         }
     }
 
+    /// Called by the producer once its computation finishes, so the next
+    /// request for `key` starts a fresh run instead of subscribing to a
+    /// channel nobody is feeding anymore.
+    pub fn finish_match_stream(&self, key: &[u64]) {
+        self.in_flight.remove(key);
+    }
+
+    /// Snapshot of whatever has already been cached for `key`, in the
+    /// underlying map's iteration order (not insertion order). Used to
+    /// replay results to a subscriber
+    /// that joins an in-flight computation mid-stream, before it switches to
+    /// the live broadcast feed.
+    pub fn cached_matches(&self, key: &[u64]) -> Vec<Arc<XmapMatch>> {
+        self.match_cache
+            .get(key)
+            .map(|matches| matches.value().iter().map(|e| Arc::clone(e.value())).collect())
+            .unwrap_or_default()
+    }
+
     /// Gets parsed records from cache or parses new content
     ///
     /// # Arguments
@@ -318,6 +468,31 @@ impl XmapCache {
         Ok((records, chr_lengths))
     }
 
+    /// Resolves the result of a [`StreamingXmapParser::finish`] against the
+    /// cache: returns the already-cached records for `hash` if another
+    /// request already parsed this exact content, otherwise inserts and
+    /// returns the freshly streamed ones.
+    ///
+    /// # Arguments
+    /// * `hash` - Content hash produced while streaming the field
+    /// * `records` / `chr_lengths` - Records parsed from the streamed chunks
+    pub fn get_or_insert_streamed(
+        &self,
+        hash: u64,
+        records: Arc<DashMap<u32, Arc<XmapRecord>>>,
+        chr_lengths: Arc<DashMap<u8, f64>>,
+    ) -> (Arc<DashMap<u32, Arc<XmapRecord>>>, Arc<DashMap<u8, f64>>) {
+        if let Some(cached_records) = self.parsed_files.get(&hash) {
+            if let Some(cached_lengths) = self.chromosome_lengths.get(&hash) {
+                return (Arc::clone(cached_records.value()), Arc::clone(cached_lengths.value()));
+            }
+        }
+
+        self.parsed_files.insert(hash, Arc::clone(&records));
+        self.chromosome_lengths.insert(hash, Arc::clone(&chr_lengths));
+        (records, chr_lengths)
+    }
+
     /// Gets index from cache or builds new index
     ///
     /// # Arguments
@@ -349,9 +524,7 @@ impl XmapCache {
             .value()
             .clone();
 
-        let match_id = (match_data.qry_contig_id as u64) << 32
-            | (match_data.records[0].qry_start_pos as u64);
-        matches.insert(match_id, match_data);
+        matches.insert(match_id(&match_data), match_data);
     }
 }
 
@@ -363,11 +536,8 @@ impl XmapCache {
 /// # Returns
 /// * `u64` - Content hash
 pub fn hash_content(content: &str) -> u64 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-
     let mut hasher = DefaultHasher::new();
-    content.hash(&mut hasher);
+    hasher.write(content.as_bytes());
     hasher.finish()
 }
 
@@ -397,6 +567,51 @@ mod tests {
         assert_eq!(rec1.ref_len, 117599.0);
     }
 
+    #[test]
+    fn test_streaming_parser_matches_whole_buffer() {
+        let content = sample_xmap_content();
+        let (whole_records, whole_chr_lengths) = parse_xmap_file(content).unwrap();
+        let whole_hash = hash_content(content);
+
+        // Feed the same bytes in small, arbitrary-width chunks, including
+        // one that splits a line in the middle of a field.
+        let mut parser = StreamingXmapParser::new(content.len() * 2);
+        for chunk in content.as_bytes().chunks(7) {
+            parser.feed(chunk).unwrap();
+        }
+        let (streamed_hash, streamed_records, streamed_chr_lengths) = parser.finish().unwrap();
+
+        assert_eq!(streamed_hash, whole_hash);
+        assert_eq!(streamed_records.len(), whole_records.len());
+        assert_eq!(streamed_chr_lengths.len(), whole_chr_lengths.len());
+
+        for entry in whole_records.iter() {
+            let streamed = streamed_records.get(entry.key()).unwrap();
+            assert_eq!(streamed.qry_contig_id, entry.qry_contig_id);
+            assert_eq!(streamed.ref_contig_id, entry.ref_contig_id);
+            assert_eq!(streamed.confidence, entry.confidence);
+        }
+    }
+
+    #[test]
+    fn test_streaming_parser_rejects_oversized_field() {
+        let content = sample_xmap_content();
+        let mut parser = StreamingXmapParser::new(16);
+
+        let mut result = Ok(());
+        for chunk in content.as_bytes().chunks(7) {
+            result = parser.feed(chunk);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        match result {
+            Err(StreamParseError::TooLarge { cap }) => assert_eq!(cap, 16),
+            other => panic!("expected TooLarge, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_build_index() {
         let (records, _) = parse_xmap_file(sample_xmap_content()).unwrap();
@@ -0,0 +1,167 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use anyhow::Context;
+use dashmap::DashMap;
+
+use crate::functions::extract_sequence_2bit_in_chunks;
+use crate::pool::TwoBitPool;
+
+/// Base used by the rolling fingerprint in [`find_cdc_cut`]. Arbitrary odd
+/// 64-bit constant; only needs to mix bits well, not be cryptographic.
+const ROLLING_BASE: u64 = 1_099_511_628_211;
+
+/// Boundary parameters for content-defined chunking.
+#[derive(Clone, Copy, Debug)]
+pub struct CdcParams {
+    /// Width of the rolling-hash window, in bytes.
+    pub window_size: usize,
+    /// Target average chunk size; boundaries are cut so the low bits of
+    /// the fingerprint hit a mask sized to this target.
+    pub avg_chunk_size: usize,
+    /// A boundary found before this many bytes is ignored (keeps chunks
+    /// from degenerating to near-zero length).
+    pub min_chunk_size: usize,
+    /// A chunk is force-cut at this length even with no boundary hit.
+    pub max_chunk_size: usize,
+}
+
+impl Default for CdcParams {
+    fn default() -> Self {
+        Self {
+            window_size: 48,
+            avg_chunk_size: 4096,
+            min_chunk_size: 1024,
+            max_chunk_size: 16384,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ChunkSpan {
+    hash: u64,
+    len: usize,
+}
+
+/// Shared dedup store for content-defined chunks of extracted genome
+/// sequence.
+///
+/// `chunks` interns chunk bytes by content hash, so two chunks with
+/// identical bases share one allocation. `boundaries` remembers where an
+/// earlier extraction already cut a chunk starting at a given `(chr,
+/// position)`, so a later mapping whose interval starts at the same
+/// position is served from `chunks` instead of re-reading the 2bit file.
+///
+/// One store per genome: chromosome names aren't unique across genomes, so
+/// CHM13 and HG38 each need their own to avoid cross-genome collisions.
+#[derive(Default)]
+pub struct CdcStore {
+    chunks: DashMap<u64, Arc<str>>,
+    boundaries: DashMap<(Arc<str>, usize), ChunkSpan>,
+}
+
+impl CdcStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn hash_chunk(bytes: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(bytes.as_bytes());
+    hasher.finish()
+}
+
+/// Extracts `chr:start-end` the same way
+/// [`extract_sequence_2bit_in_chunks`] does, but splits the region into
+/// content-defined chunks and serves any chunk already seen by an earlier,
+/// overlapping mapping from `store` instead of re-reading the 2bit file.
+///
+/// The reassembled sequence is byte-identical to the naive extraction:
+/// content-defined chunks only change *where* bytes are cut and cached,
+/// never the bytes themselves.
+pub fn extract_sequence_cdc(
+    genome: &Arc<TwoBitPool>,
+    store: &CdcStore,
+    chr: &str,
+    start: usize,
+    end: usize,
+    params: &CdcParams,
+) -> anyhow::Result<String> {
+    let chr: Arc<str> = Arc::from(chr);
+    let mut sequence = String::with_capacity(end - start);
+    let mut pos = start;
+
+    while pos < end {
+        if let Some(reused) = try_reuse_chunk(store, &chr, pos, end) {
+            sequence.push_str(&reused.0);
+            pos += reused.1;
+            continue;
+        }
+
+        let window_end = (pos + params.max_chunk_size).min(end);
+        let raw = extract_sequence_2bit_in_chunks(genome, &chr, pos, window_end)
+            .context("Failed to extract sequence for content-defined chunking")?;
+
+        let cut = find_cdc_cut(raw.as_bytes(), params);
+        let chunk_bytes = &raw[..cut];
+        let hash = hash_chunk(chunk_bytes);
+
+        let interned = store
+            .chunks
+            .entry(hash)
+            .or_insert_with(|| Arc::from(chunk_bytes))
+            .value()
+            .clone();
+
+        store.boundaries.insert((Arc::clone(&chr), pos), ChunkSpan { hash, len: cut });
+
+        sequence.push_str(&interned);
+        pos += cut;
+    }
+
+    Ok(sequence)
+}
+
+/// If a previous extraction already cut a chunk starting exactly at `pos`
+/// and that chunk fits within `[pos, end)`, returns its interned bytes and
+/// length.
+fn try_reuse_chunk(store: &CdcStore, chr: &Arc<str>, pos: usize, end: usize) -> Option<(Arc<str>, usize)> {
+    let span = *store.boundaries.get(&(Arc::clone(chr), pos))?.value();
+    if pos + span.len > end {
+        return None;
+    }
+    let chunk = store.chunks.get(&span.hash)?.value().clone();
+    Some((chunk, span.len))
+}
+
+/// Scans `data` for the first content-defined chunk boundary using a
+/// Rabin-style rolling fingerprint over a `window_size`-byte sliding
+/// window, cutting as soon as the low bits of the fingerprint match the
+/// target mask. Falls back to `max_chunk_size`, or to the end of `data`
+/// if that comes first, when no boundary is found.
+fn find_cdc_cut(data: &[u8], params: &CdcParams) -> usize {
+    let window = params.window_size.max(1);
+    let mask = (params.avg_chunk_size.next_power_of_two() as u64).saturating_sub(1);
+    let base_pow_window = ROLLING_BASE.wrapping_pow(window as u32);
+
+    let mut fingerprint: u64 = 0;
+    for (offset, &byte) in data.iter().enumerate() {
+        fingerprint = fingerprint.wrapping_mul(ROLLING_BASE).wrapping_add(byte as u64);
+        if offset >= window {
+            let outgoing = data[offset - window] as u64;
+            fingerprint = fingerprint.wrapping_sub(outgoing.wrapping_mul(base_pow_window));
+        }
+
+        let chunk_len = offset + 1;
+        if chunk_len >= window && chunk_len >= params.min_chunk_size && (fingerprint & mask) == 0 {
+            return chunk_len;
+        }
+        if chunk_len >= params.max_chunk_size {
+            return chunk_len;
+        }
+    }
+
+    data.len()
+}
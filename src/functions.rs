@@ -1,33 +1,45 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use anyhow::Context;
 use rayon::iter::IntoParallelRefIterator;
-use twobit::TwoBitPhysicalFile;
+use crate::cdc::{extract_sequence_cdc, CdcParams};
+use crate::pool::TwoBitPool;
 use crate::prelude::{SequenceData, CHUNK_SIZE};
 use rayon::prelude::*;
 // use tokio version instead
 use tokio::fs::File;
 use tokio::io::{AsyncBufReadExt, BufReader};
 
-pub async fn read_sequence(fasta_path: &str) -> anyhow::Result<String> {
-    let file = File::open(fasta_path)
-        .await
-        .context(format!("Failed to open FASTA file: {}", fasta_path))?;
+#[cfg(all(feature = "uring", target_os = "linux"))]
+mod uring_io;
 
-    let reader = BufReader::new(file);
-    let mut seq = String::new();
-    let mut in_sequence = false;
-    let mut lines = reader.lines();
+pub async fn read_sequence(fasta_path: &str) -> anyhow::Result<String> {
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    {
+        return uring_io::read_sequence_uring(fasta_path);
+    }
 
-    while let Some(line) = lines.next_line().await? {
-        if line.starts_with('>') {
-            in_sequence = true; // Skip header
-        } else if in_sequence {
-            seq.push_str(&line.trim());
+    #[cfg(not(all(feature = "uring", target_os = "linux")))]
+    {
+        let file = File::open(fasta_path)
+            .await
+            .context(format!("Failed to open FASTA file: {}", fasta_path))?;
+
+        let reader = BufReader::new(file);
+        let mut seq = String::new();
+        let mut in_sequence = false;
+        let mut lines = reader.lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.starts_with('>') {
+                in_sequence = true; // Skip header
+            } else if in_sequence {
+                seq.push_str(&line.trim());
+            }
         }
-    }
 
-    Ok(seq)
+        Ok(seq)
+    }
 }
 
 pub async fn parse_alignment_file(alignment_file_path: &str) -> anyhow::Result<Vec<(String, i64, i64, String, i64, i64)>> {
@@ -63,34 +75,38 @@ pub async fn parse_alignment_file(alignment_file_path: &str) -> anyhow::Result<V
 }
 
 pub fn extract_sequence_2bit_in_chunks(
-    genome: &Arc<Mutex<TwoBitPhysicalFile>>, // One thread can read
+    genome: &Arc<TwoBitPool>, // Each caller checks out its own handle, so concurrent reads don't serialize
     chr: &str,
     start: usize,
     end: usize,
 ) -> anyhow::Result<String> {
-    // Use map_err to convert PoisonError to an anyhow error
-    let mut genome_lock = genome
-        .lock()
-        .map_err(|e| anyhow::anyhow!("Failed to lock genome file for reading: {}", e))?;;
-
-
-    let sequence_length = end - start;
-    let mut sequence = String::with_capacity(sequence_length); // For now
-    let mut chunk_start = start;
-
-    while chunk_start < end {
-        let chunk_end = (chunk_start + CHUNK_SIZE).min(end);
-        match genome_lock
-            .read_sequence(chr, chunk_start..chunk_end)
-            .context("Failed to read sequence chunk")
-        {
-            Ok(chunk) => sequence.push_str(&chunk),
-            Err(e) => return Err(e),
-        }
-        chunk_start = chunk_end;
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    {
+        return uring_io::extract_sequence_2bit_in_chunks_uring(genome, chr, start, end);
     }
 
-    Ok(sequence)
+    #[cfg(not(all(feature = "uring", target_os = "linux")))]
+    {
+        let mut handle = genome.checkout();
+
+        let sequence_length = end - start;
+        let mut sequence = String::with_capacity(sequence_length); // For now
+        let mut chunk_start = start;
+
+        while chunk_start < end {
+            let chunk_end = (chunk_start + CHUNK_SIZE).min(end);
+            match handle
+                .read_sequence(chr, chunk_start..chunk_end)
+                .context("Failed to read sequence chunk")
+            {
+                Ok(chunk) => sequence.push_str(&chunk),
+                Err(e) => return Err(e),
+            }
+            chunk_start = chunk_end;
+        }
+
+        Ok(sequence)
+    }
 }
 
 pub async fn find_mappings(
@@ -100,6 +116,7 @@ pub async fn find_mappings(
 ) -> anyhow::Result<bool> {
     let count = Arc::new(AtomicUsize::new(0)); // Thread safe
     let match_found = Arc::new(AtomicBool::new(false));
+    let cdc_params = CdcParams::default();
 
     mappings.par_iter().for_each(|mapping| { // Par here
         let match_found = Arc::clone(&match_found);
@@ -107,22 +124,30 @@ pub async fn find_mappings(
         let query_seq = query_seq.to_string();
         let sequence_data = sequence_data.clone();
 
-        let chm13_seq_result = extract_sequence_2bit_in_chunks(
+        // Mappings frequently reference overlapping or repeated genome
+        // intervals, so extraction goes through the content-defined
+        // chunking dedup store rather than re-reading the same bases
+        // from the 2bit file for every mapping that touches them.
+        let chm13_seq_result = extract_sequence_cdc(
             &sequence_data.chm13_2bit,
+            &sequence_data.chm13_cdc,
             &mapping.0,
             mapping.1 as usize,
-            mapping.2 as usize
+            mapping.2 as usize,
+            &cdc_params,
         )
             .context(format!(
                 "Error extracting CHM13 sequence for {}:{}-{}",
                 mapping.0, mapping.1, mapping.2
             ));
 
-        let hg38_seq_result = extract_sequence_2bit_in_chunks(
+        let hg38_seq_result = extract_sequence_cdc(
             &sequence_data.hg38_2bit,
+            &sequence_data.hg38_cdc,
             &mapping.3,
             mapping.4 as usize,
-            mapping.5 as usize
+            mapping.5 as usize,
+            &cdc_params,
         )
             .context(format!(
                 "Error extracting HG38 sequence for {}:{}-{}",
@@ -2,18 +2,18 @@ use rayon::prelude::*;
 use std::fs::File;
 use std::io::Write;
 use std::io::BufRead;
-use std::sync::{Arc, Mutex};
 use std::error::Error;
-use twobit::TwoBitFile;
 use rayon::ThreadPoolBuilder;
 use anyhow::Context;
 
 #[cfg(test)]
 mod tests;
 
+mod cdc;
 mod constants;
 mod resources;
 mod functions;
+mod pool;
 mod prelude;
 
 use crate::prelude::*;
@@ -28,15 +28,8 @@ async fn main() -> anyhow::Result<()> {
     let query_sequence = read_sequence(fasta_path).await?;
     let alignment_mappings = parse_alignment_file(alignment_file_path).await?;
 
-    let chm13_2bit = Arc::new(Mutex::new(TwoBitFile::open(chm13_2bit_path)?));
-    let hg38_2bit = Arc::new(Mutex::new(TwoBitFile::open(hg38_2bit_path)?));
-
-    let sequence_data = SequenceData {
-        chm13_2bit,
-        hg38_2bit,
-    };
-
     let num_threads = num_cpus::get();
+    let sequence_data = SequenceData::open(chm13_2bit_path, hg38_2bit_path, Some(num_threads))?;
     println!("Using {} threads.", num_threads);
 
     ThreadPoolBuilder::new()
@@ -0,0 +1,113 @@
+use anyhow::Context;
+use crossbeam::channel::{bounded, Receiver, Sender};
+use twobit::{TwoBitFile, TwoBitPhysicalFile};
+
+/// Pool of independently-opened 2bit file handles to the same path.
+///
+/// `find_mappings` fans its work out over `rayon`, but a single
+/// `Mutex<TwoBitPhysicalFile>` serializes every read behind one lock no
+/// matter how many workers are available. `TwoBitPool` instead opens `size`
+/// separate handles up front and hands them out over a bounded channel, so
+/// concurrent workers read truly in parallel.
+pub struct TwoBitPool {
+    path: String,
+    checkin: Sender<TwoBitPhysicalFile>,
+    checkout: Receiver<TwoBitPhysicalFile>,
+}
+
+impl TwoBitPool {
+    /// Opens `size` independent handles to `path`.
+    pub fn open(path: &str, size: usize) -> anyhow::Result<Self> {
+        let (checkin, checkout) = bounded(size);
+        for _ in 0..size {
+            let handle = TwoBitFile::open(path)
+                .context(format!("Failed to open 2bit file: {}", path))?;
+            checkin
+                .send(handle)
+                .expect("pool channel was just created with capacity `size`");
+        }
+
+        Ok(Self { path: path.to_string(), checkin, checkout })
+    }
+
+    /// Checks out a handle, blocking until one is free. The handle is
+    /// returned to the pool when the guard is dropped.
+    pub fn checkout(&self) -> PooledHandle<'_> {
+        let handle = self
+            .checkout
+            .recv()
+            .expect("pool outlives every handle it hands out");
+        PooledHandle { pool: self, handle: Some(handle) }
+    }
+
+    /// Path this pool's handles were opened from, so a fast-path reader
+    /// (e.g. the `uring` feature's 2bit backend) can reopen the same file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// A handle checked out from a [`TwoBitPool`]. Returns itself to the pool
+/// on drop.
+pub struct PooledHandle<'a> {
+    pool: &'a TwoBitPool,
+    handle: Option<TwoBitPhysicalFile>,
+}
+
+impl std::ops::Deref for PooledHandle<'_> {
+    type Target = TwoBitPhysicalFile;
+
+    fn deref(&self) -> &TwoBitPhysicalFile {
+        self.handle.as_ref().expect("handle is only taken on drop")
+    }
+}
+
+impl std::ops::DerefMut for PooledHandle<'_> {
+    fn deref_mut(&mut self) -> &mut TwoBitPhysicalFile {
+        self.handle.as_mut().expect("handle is only taken on drop")
+    }
+}
+
+impl Drop for PooledHandle<'_> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = self.pool.checkin.send(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_roundtrip() {
+        let pool = TwoBitPool::open("chm13.2bit", 2).unwrap();
+        assert_eq!(pool.path(), "chm13.2bit");
+    }
+
+    #[test]
+    fn test_checkout_returns_handle_to_pool_on_drop() {
+        let pool = TwoBitPool::open("chm13.2bit", 1);
+        let pool = pool.unwrap();
+
+        // With only one handle in the pool, a second checkout would block
+        // forever if the first wasn't returned on drop.
+        {
+            let _handle = pool.checkout();
+        }
+        let _handle = pool.checkout();
+    }
+
+    #[test]
+    fn test_pool_hands_out_distinct_handles_up_to_size() {
+        let pool = TwoBitPool::open("chm13.2bit", 2).unwrap();
+
+        let first = pool.checkout();
+        let second = pool.checkout();
+        // Both checked out at once without blocking proves `size` handles
+        // were actually opened, not just one shared behind the channel.
+        drop(first);
+        drop(second);
+    }
+}
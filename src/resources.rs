@@ -1,8 +1,29 @@
-use std::sync::{Arc, Mutex};
-use twobit::TwoBitPhysicalFile;
+use std::sync::Arc;
+use crate::cdc::CdcStore;
+use crate::pool::TwoBitPool;
 
 #[derive(Clone)]
 pub struct SequenceData {
-    pub chm13_2bit: Arc<Mutex<TwoBitPhysicalFile>>,
-    pub hg38_2bit: Arc<Mutex<TwoBitPhysicalFile>>,
+    pub chm13_2bit: Arc<TwoBitPool>,
+    pub hg38_2bit: Arc<TwoBitPool>,
+    /// Content-defined-chunk dedup store for CHM13, shared across every
+    /// mapping extracted through this `SequenceData`.
+    pub chm13_cdc: Arc<CdcStore>,
+    /// Content-defined-chunk dedup store for HG38.
+    pub hg38_cdc: Arc<CdcStore>,
+}
+
+impl SequenceData {
+    /// Opens pooled handles for both genomes. `pool_size` defaults to the
+    /// rayon thread count when `None`.
+    pub fn open(chm13_path: &str, hg38_path: &str, pool_size: Option<usize>) -> anyhow::Result<Self> {
+        let pool_size = pool_size.unwrap_or_else(rayon::current_num_threads);
+
+        Ok(Self {
+            chm13_2bit: Arc::new(TwoBitPool::open(chm13_path, pool_size)?),
+            hg38_2bit: Arc::new(TwoBitPool::open(hg38_path, pool_size)?),
+            chm13_cdc: Arc::new(CdcStore::new()),
+            hg38_cdc: Arc::new(CdcStore::new()),
+        })
+    }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use super::*;
+use crate::cdc::{extract_sequence_cdc, CdcParams};
 
 // Helper function to create a temp file for testing
 fn create_test_fasta(contents: &str) -> String {
@@ -48,13 +49,7 @@ async fn test_extract_sequence_2bit_in_chunks() {
     let chm13_2bit_path = "chm13.2bit";
     let hg38_2bit_path = "hg38.2bit";
 
-    let chm13_2bit = Arc::new(Mutex::new(TwoBitFile::open(chm13_2bit_path).unwrap()));
-    let hg38_2bit = Arc::new(Mutex::new(TwoBitFile::open(hg38_2bit_path).unwrap()));
-
-    let sequence_data = SequenceData {
-        chm13_2bit,
-        hg38_2bit,
-    };
+    let sequence_data = SequenceData::open(chm13_2bit_path, hg38_2bit_path, None).unwrap();
 
     let chr = "chr1";
     let start = 0;
@@ -68,6 +63,31 @@ async fn test_extract_sequence_2bit_in_chunks() {
     assert!(seq.len() > 0);
 }
 
+#[tokio::test]
+async fn test_extract_sequence_cdc_matches_naive() {
+    let chm13_2bit_path = "chm13.2bit";
+    let hg38_2bit_path = "hg38.2bit";
+
+    let sequence_data = SequenceData::open(chm13_2bit_path, hg38_2bit_path, None).unwrap();
+
+    let chr = "chr1";
+    let start = 0;
+    let end = 5000;
+    let params = CdcParams::default();
+
+    let naive = extract_sequence_2bit_in_chunks(&sequence_data.chm13_2bit, chr, start, end).unwrap();
+
+    // First call: cache miss, chunks get cut and interned.
+    let cdc_first = extract_sequence_cdc(&sequence_data.chm13_2bit, &sequence_data.chm13_cdc, chr, start, end, &params).unwrap();
+    assert_eq!(cdc_first, naive);
+
+    // Second call over the same interval: every chunk is now a boundary
+    // hit, so this exercises try_reuse_chunk's cache-hit path instead of
+    // re-reading the 2bit file.
+    let cdc_second = extract_sequence_cdc(&sequence_data.chm13_2bit, &sequence_data.chm13_cdc, chr, start, end, &params).unwrap();
+    assert_eq!(cdc_second, naive);
+}
+
 #[tokio::test]
 async fn test_find_mappings() {
     let fasta_path = "query.fasta";
@@ -87,13 +107,7 @@ async fn test_find_mappings() {
     file.write_all(alignment_data.as_bytes()).unwrap();
     let mappings = parse_alignment_file(alignment_path).await.unwrap();
 
-    let chm13_2bit = Arc::new(Mutex::new(TwoBitFile::open(chm13_2bit_path).unwrap()));
-    let hg38_2bit = Arc::new(Mutex::new(TwoBitFile::open(hg38_2bit_path).unwrap()));
-
-    let sequence_data = SequenceData {
-        chm13_2bit,
-        hg38_2bit,
-    };
+    let sequence_data = SequenceData::open(chm13_2bit_path, hg38_2bit_path, None).unwrap();
 
     let match_found = find_mappings(&query_sequence, &mappings, &sequence_data).await.unwrap();
     assert!(match_found, "No match was found!");